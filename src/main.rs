@@ -1,28 +1,142 @@
-use std::{error::Error, path::PathBuf, time::UNIX_EPOCH};
+use std::{
+    collections::HashSet,
+    io::{Read, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::UNIX_EPOCH,
+};
 
 use clap::{CommandFactory, Parser};
 use clap_complete::{Shell, generate};
 use directories::BaseDirs;
-use niri_ipc::{Request, Response, socket::Socket};
+use niri_ipc::{Event, Request, Response, socket::Socket};
 use serde::{Deserialize, Serialize};
 
-type CarouselResult<T> = Result<T, Box<dyn Error>>;
+/// Name of the daemon's local control socket, kept next to the state file in the data dir.
+const DAEMON_SOCKET_NAME: &str = "daemon.sock";
 
-#[derive(Debug, derive_more::Display)]
+type CarouselResult<T> = Result<T, CarouselError>;
+
+#[derive(Debug, thiserror::Error)]
 enum CarouselError {
-    #[display(
+    #[error(
         "You're running this application either as root or as another user that don't have home directory."
     )]
     InvalidRun,
-    #[display("There's something wrong with niri IPC. Check your application and niri version.")]
+    #[error("There's something wrong with niri IPC. Check your application and niri version.")]
     IpcProblems,
-    #[display(
+    #[error("niri rejected the request: {0}")]
+    IpcRejected(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(
         "Invalid passed max duration to set. Required to be in range [0.2; 1.0], but given {max_duration}"
     )]
     IncorrectMaxDuration { max_duration: Duration },
+    #[error("failed to read {path}")]
+    Read {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("failed to write {path}")]
+    Write {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("failed to parse state file {path}, it may be corrupted")]
+    StateParse {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("failed to serialize carousel state")]
+    StateSerialize(#[from] serde_json::Error),
+    #[error("failed to parse config file {path}")]
+    ConfigParse {
+        path: PathBuf,
+        #[source]
+        source: toml::de::Error,
+    },
+    #[error("failed to serialize config")]
+    ConfigSerialize(#[from] toml::ser::Error),
+}
+
+// Manual impl instead of `#[from]`: niri's IPC rejection is a plain `String`, which doesn't
+// implement `std::error::Error`, so it can't satisfy `#[from]`'s source-chain requirement.
+impl From<String> for CarouselError {
+    fn from(message: String) -> Self {
+        CarouselError::IpcRejected(message)
+    }
+}
+
+/// User-editable preferences, kept apart from `CarouselData`'s runtime bookkeeping so that
+/// tweaking settings never disturbs (or gets wiped alongside) the carousel's MRU history.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+struct Config {
+    max_duration: Duration,
+    /// Explicit initial MRU layout ordering, by niri layout index, front = most recent.
+    /// Falls back to niri's own order when absent or when it is not a permutation of niri's
+    /// current layout indices (wrong length, duplicates or out-of-range entries).
+    initial_layouts: Option<Vec<usize>>,
+    /// Whether a single keypress within `max_duration` toggles straight to the previous
+    /// layout (`true`, the default) or just holds in place until a rapid second tap starts
+    /// cycling forward through the MRU list.
+    single_tap_toggles: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            max_duration: Duration::default(),
+            initial_layouts: None,
+            single_tap_toggles: true,
+        }
+    }
 }
 
-impl Error for CarouselError {}
+impl Config {
+    fn get_path() -> CarouselResult<PathBuf> {
+        let mut config_directory = BaseDirs::new()
+            .ok_or(CarouselError::InvalidRun)?
+            .config_dir()
+            .to_path_buf();
+        config_directory.push("layout-carousel-niri");
+        if !config_directory.exists() {
+            std::fs::create_dir_all(&config_directory)
+                .map_err(|source| CarouselError::Write {
+                    path: config_directory.clone(),
+                    source,
+                })?;
+        }
+        config_directory.push("config.toml");
+        Ok(config_directory)
+    }
+
+    fn load() -> CarouselResult<Self> {
+        let path = Self::get_path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let raw = std::fs::read_to_string(&path).map_err(|source| CarouselError::Read {
+            path: path.clone(),
+            source,
+        })?;
+        toml::from_str(&raw).map_err(|source| CarouselError::ConfigParse { path, source })
+    }
+
+    fn dump(&self) -> CarouselResult<()> {
+        let path = Self::get_path()?;
+        let serialized = toml::to_string_pretty(self)?;
+        std::fs::write(&path, serialized).map_err(|source| CarouselError::Write { path, source })
+    }
+}
 
 /// The layout carousel for niri WM. Switches layouts in comfort way like MacOS.
 #[derive(Parser)]
@@ -33,54 +147,138 @@ enum LayoutCarouselCmd {
     KeypressDuration { duration: Option<f64> },
     /// Resetting all settings to default according to niri config file.
     Reload,
+    /// Runs in the foreground, keeping carousel state resident in memory and watching niri
+    /// for layout changes made outside this tool, instead of reloading the state file on
+    /// every `Switch`.
+    Daemon,
+    /// Prints the current carousel state as JSON, for status bar / widget integration.
+    Status {
+        /// Keep the process running, emitting a fresh JSON line on every layout change.
+        #[arg(long)]
+        watch: bool,
+    },
     /// Prints the completion code for a specific shell.
     Completion { shell: Option<Shell> },
 }
 
+/// Current on-disk schema of `CarouselData`. Bump this whenever the JSON shape changes, and
+/// give `CarouselData::migrate` a branch for upgrading a file stamped with an older one.
+const CURRENT_SCHEMA_VERSION: u8 = 1;
+
 #[derive(Serialize, Deserialize)]
 struct CarouselData {
+    #[serde(default)]
+    version: u8,
     last_time: f64,
+    /// MRU-ordered niri layout indices; the front is the most recently committed layout.
+    /// The layout currently shown is `layouts[cursor]`.
     layouts: Vec<usize>,
-    index_frequent: usize,
-    index_rotational: usize,
+    /// Index into `layouts` of the entry currently being previewed while cycling.
+    cursor: usize,
     sum_time: f64,
     counter: u8,
 
-    #[serde(default)]
+    // INFO: config-sourced, re-read from `Config::load()` on every load rather than
+    // persisted alongside the runtime bookkeeping above.
+    #[serde(skip)]
     max_duration: Duration,
+    #[serde(skip)]
+    single_tap_toggles: bool,
 }
 
 impl CarouselData {
     fn create_default(socket: &mut Socket) -> CarouselResult<Self> {
         let Response::KeyboardLayouts(layouts) = socket.send(Request::KeyboardLayouts)?? else {
-            return Err(Box::new(CarouselError::IpcProblems));
+            return Err(CarouselError::IpcProblems);
         };
+        let config = Config::load()?;
 
         Ok(CarouselData {
+            version: CURRENT_SCHEMA_VERSION,
             last_time: std::time::SystemTime::now()
                 .duration_since(UNIX_EPOCH)
                 .expect("Time after UNIX epoch")
                 .as_secs_f64(),
             counter: 0,
-            layouts: (0..layouts.names.len()).collect(),
-            index_frequent: 0,
-            index_rotational: 0,
+            layouts: config
+                .initial_layouts
+                .filter(|order| is_valid_layout_permutation(order, layouts.names.len()))
+                .unwrap_or_else(|| (0..layouts.names.len()).collect()),
+            cursor: 0,
             sum_time: 0.0,
-            max_duration: Duration::default(),
+            max_duration: config.max_duration,
+            single_tap_toggles: config.single_tap_toggles,
         })
     }
 
-    fn load() -> CarouselResult<Self> {
-        Ok(serde_json::from_str(&std::fs::read_to_string(
-            Self::get_path(false)?,
-        )?)?)
+    fn load(socket: &mut Socket) -> CarouselResult<Self> {
+        let path = Self::get_path(false)?;
+        let raw = std::fs::read_to_string(&path).map_err(|source| CarouselError::Read {
+            path: path.clone(),
+            source,
+        })?;
+
+        let mut data = match serde_json::from_str::<CarouselData>(&raw) {
+            Ok(data) if data.version == CURRENT_SCHEMA_VERSION => data,
+            Ok(data) => Self::migrate(data),
+            Err(source) => Self::recover(socket, &path, &raw, source)?,
+        };
+
+        let config = Config::load()?;
+        data.max_duration = config.max_duration;
+        data.single_tap_toggles = config.single_tap_toggles;
+        Ok(data)
+    }
+
+    /// Upgrades a successfully-parsed but out-of-date state file. There have been no schema
+    /// changes since version 1 yet, so this currently only stamps the current version.
+    fn migrate(mut data: CarouselData) -> Self {
+        data.version = CURRENT_SCHEMA_VERSION;
+        data
+    }
+
+    /// Best-effort recovery from a state file that failed to parse outright: salvage the MRU
+    /// ordering and cursor if they're still individually readable, and rebuild everything
+    /// else from niri's current layouts, rather than losing the whole carousel history.
+    fn recover(
+        socket: &mut Socket,
+        path: &Path,
+        raw: &str,
+        source: serde_json::Error,
+    ) -> CarouselResult<Self> {
+        let parse_error = CarouselError::StateParse {
+            path: path.to_path_buf(),
+            source,
+        };
+        eprintln!("warning: {parse_error}; recovering what can be salvaged");
+
+        let mut data = Self::create_default(socket)?;
+
+        if let Ok(salvaged) = serde_json::from_str::<serde_json::Value>(raw) {
+            if let Some(layouts) = salvaged
+                .get("layouts")
+                .and_then(|value| serde_json::from_value::<Vec<usize>>(value.clone()).ok())
+                .filter(|order| is_valid_layout_permutation(order, data.layouts.len()))
+            {
+                data.layouts = layouts;
+            }
+            if let Some(cursor) = salvaged
+                .get("cursor")
+                .and_then(serde_json::Value::as_u64)
+                .map(|cursor| cursor as usize)
+                .filter(|&cursor| cursor < data.layouts.len())
+            {
+                data.cursor = cursor;
+            }
+        }
+
+        Ok(data)
     }
 
     fn dump(&self) -> CarouselResult<()> {
-        Ok(std::fs::write(
-            Self::get_path(true)?,
-            serde_json::to_string(self)?,
-        )?)
+        let path = Self::get_path(true)?;
+        let serialized = serde_json::to_string(self)?;
+        std::fs::write(&path, serialized).map_err(|source| CarouselError::Write { path, source })
     }
 
     fn get_path(create_directory: bool) -> CarouselResult<PathBuf> {
@@ -90,49 +288,65 @@ impl CarouselData {
             .to_path_buf();
         data_directory.push("layout-carousel-niri");
         if !data_directory.exists() && create_directory {
-            std::fs::create_dir_all(&data_directory)?;
+            std::fs::create_dir_all(&data_directory)
+                .map_err(|source| CarouselError::Write {
+                    path: data_directory.clone(),
+                    source,
+                })?;
         }
         data_directory.push("data");
         Ok(data_directory)
     }
 
+    /// Path to the daemon's local control socket, living next to the state file.
+    fn get_daemon_socket_path() -> CarouselResult<PathBuf> {
+        let mut socket_path = Self::get_path(false)?;
+        socket_path.set_file_name(DAEMON_SOCKET_NAME);
+        Ok(socket_path)
+    }
+
     fn compute_time_and_count(&mut self, call_time: f64) {
         let diff = call_time - self.last_time;
         self.last_time = call_time;
-
         self.sum_time += diff;
+
         if self.max_duration.satisfies(self.sum_time) {
             self.counter += 1;
-        } else {
-            self.sum_time = 0.0;
-            self.counter = 1;
+            if self.counter > 1 {
+                self.sum_time = 0.0;
+            }
+            return;
         }
 
-        if self.counter > 1 {
-            self.sum_time = 0.0;
+        // INFO: the tap window elapsed, which is the commit moment for whatever was being
+        // previewed by the sequence that just ended: it becomes the new MRU head.
+        if self.counter > 0 {
+            let committed = self.layouts.remove(self.cursor);
+            self.layouts.insert(0, committed);
         }
+        self.sum_time = 0.0;
+        self.counter = 1;
+        self.cursor = 0;
     }
 
     fn handle_switch(&mut self) {
         if self.counter <= 1 {
-            self.index_frequent = (self.index_frequent + 1) % 2;
+            self.cursor = if self.single_tap_toggles { 1 } else { 0 };
         } else {
-            if self.counter > 2 {
-                self.index_rotational += 1;
-            } else {
-                // INFO: need to turn back to previous layout to switch it to any picked by user.
-                self.index_frequent = (self.index_frequent + 1) % 2;
-                self.index_rotational = 2;
-            }
-
-            self.index_rotational %= self.layouts.len();
-
-            self.layouts
-                .swap(self.index_frequent, self.index_rotational);
+            self.cursor = (self.cursor + 1) % self.layouts.len();
         }
     }
 }
 
+/// Whether `order` is a valid MRU ordering for `len` layouts: the right length and a
+/// permutation of `0..len`, not merely a same-length vector that could contain duplicates or
+/// out-of-range indices and silently make a niri layout unreachable.
+fn is_valid_layout_permutation(order: &[usize], len: usize) -> bool {
+    order.len() == len
+        && order.iter().all(|&index| index < len)
+        && order.iter().collect::<HashSet<_>>().len() == len
+}
+
 #[derive(Serialize, Deserialize, derive_more::Display, Debug)]
 #[display("{_0}")]
 struct Duration(f64);
@@ -163,9 +377,11 @@ impl LayoutCarouselCmd {
         match self {
             LayoutCarouselCmd::Switch => handle_layout_switch(&mut socket),
             LayoutCarouselCmd::KeypressDuration { duration } => {
-                handle_keypress_duration(&mut socket, duration)
+                handle_keypress_duration(duration)
             }
             LayoutCarouselCmd::Reload => CarouselData::create_default(&mut socket)?.dump(),
+            LayoutCarouselCmd::Daemon => run_daemon(&mut socket),
+            LayoutCarouselCmd::Status { watch } => handle_status(socket, *watch),
             LayoutCarouselCmd::Completion { shell } => {
                 let mut command = LayoutCarouselCmd::command();
                 let name = command.get_name().to_string();
@@ -181,12 +397,79 @@ impl LayoutCarouselCmd {
     }
 }
 
+/// A single layout entry as reported by `Status`, with its niri index resolved to a name.
+#[derive(Serialize)]
+struct LayoutEntry {
+    index: usize,
+    name: String,
+}
+
+/// JSON shape printed by `Status`, for status bar / widget consumption.
+#[derive(Serialize)]
+struct StatusReport {
+    active: LayoutEntry,
+    /// The full carousel, in MRU order (front = most recently committed, which differs from
+    /// `active` while a tap sequence is still being previewed).
+    layouts: Vec<LayoutEntry>,
+    max_duration: Duration,
+}
+
+fn handle_status(mut socket: Socket, watch: bool) -> CarouselResult<()> {
+    print_status(&mut socket)?;
+    if !watch {
+        return Ok(());
+    }
+
+    let Response::Handled = socket.send(Request::EventStream)?? else {
+        return Err(CarouselError::IpcProblems);
+    };
+    let mut read_event = socket.read_events();
+    loop {
+        let event = read_event()?;
+        if matches!(
+            event,
+            Event::KeyboardLayoutSwitched { .. } | Event::KeyboardLayoutsChanged { .. }
+        ) {
+            // INFO: the socket is busy reading the event stream, so use a fresh one to
+            // query the layout names and print the refreshed status line.
+            print_status(&mut Socket::connect()?)?;
+        }
+    }
+}
+
+fn print_status(socket: &mut Socket) -> CarouselResult<()> {
+    let data = CarouselData::load(socket).or_else(|_| CarouselData::create_default(socket))?;
+    let Response::KeyboardLayouts(layouts) = socket.send(Request::KeyboardLayouts)?? else {
+        return Err(CarouselError::IpcProblems);
+    };
+
+    let entry = |index: usize| LayoutEntry {
+        index,
+        name: layouts.names[index].clone(),
+    };
+
+    let report = StatusReport {
+        active: entry(data.layouts[data.cursor]),
+        layouts: data.layouts.iter().map(|&index| entry(index)).collect(),
+        max_duration: data.max_duration,
+    };
+
+    println!("{}", serde_json::to_string(&report)?);
+    Ok(())
+}
+
 fn handle_layout_switch(socket: &mut Socket) -> CarouselResult<()> {
+    // INFO: prefer the resident daemon, if one is running, to avoid the load()/dump() round
+    // trip and its associated tap-timing jitter.
+    if notify_daemon()? {
+        return Ok(());
+    }
+
     let call_time = std::time::SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .expect("Time after UNIX epoch")
         .as_secs_f64();
-    let mut data = CarouselData::load().or_else(|_| CarouselData::create_default(socket))?;
+    let mut data = CarouselData::load(socket).or_else(|_| CarouselData::create_default(socket))?;
 
     // INFO: check is single layout in system to avoid useless computations.
     if data.layouts.len() < 2 {
@@ -197,31 +480,233 @@ fn handle_layout_switch(socket: &mut Socket) -> CarouselResult<()> {
     data.handle_switch();
 
     socket.send(Request::Action(niri_ipc::Action::SwitchLayout {
-        layout: niri_ipc::LayoutSwitchTarget::Index(data.layouts[data.index_frequent] as u8),
+        layout: niri_ipc::LayoutSwitchTarget::Index(data.layouts[data.cursor] as u8),
     }))??;
     data.dump()
 }
 
-fn handle_keypress_duration(socket: &mut Socket, duration: &mut Option<f64>) -> CarouselResult<()> {
-    let mut data = CarouselData::load().or_else(|_| CarouselData::create_default(socket))?;
+fn handle_keypress_duration(duration: &mut Option<f64>) -> CarouselResult<()> {
+    let mut config = Config::load()?;
     match duration {
         None => {
-            println!("Current max keypress duration: {}", data.max_duration);
+            println!("Current max keypress duration: {}", config.max_duration);
             Ok(())
         }
         Some(new_duration) => {
             let new_max_duration = Duration(*new_duration);
             if !new_max_duration.within_range() {
-                return Err(Box::new(CarouselError::IncorrectMaxDuration {
+                return Err(CarouselError::IncorrectMaxDuration {
                     max_duration: new_max_duration,
-                }));
+                });
             }
-            data.max_duration = new_max_duration;
-            data.dump()
+            config.max_duration = new_max_duration;
+            config.dump()
+        }
+    }
+}
+
+/// Sends a lightweight "switch" message to the daemon's local socket, if it's running.
+/// Returns `Ok(true)` when the daemon picked up the request.
+fn notify_daemon() -> CarouselResult<bool> {
+    match UnixStream::connect(CarouselData::get_daemon_socket_path()?) {
+        Ok(mut stream) => {
+            stream.write_all(b"switch")?;
+            Ok(true)
         }
+        Err(_) => Ok(false),
     }
 }
 
+/// Keeps `CarouselData` resident in memory, applying switches requested over the local
+/// socket and staying in sync with layout changes made outside this tool by watching niri's
+/// event stream, only periodically flushing state to disk.
+fn run_daemon(socket: &mut Socket) -> CarouselResult<()> {
+    let data = CarouselData::load(socket).or_else(|_| CarouselData::create_default(socket))?;
+    let data = Arc::new(Mutex::new(data));
+    let dirty = Arc::new(AtomicBool::new(false));
+
+    spawn_event_watcher(Arc::clone(&data), Arc::clone(&dirty))?;
+    spawn_periodic_flush(Arc::clone(&data), Arc::clone(&dirty));
+
+    let socket_path = CarouselData::get_daemon_socket_path()?;
+    let _ = std::fs::remove_file(&socket_path);
+    let listener = UnixListener::bind(&socket_path)?;
+
+    for stream in listener.incoming() {
+        // INFO: one garbage connection or rejected niri action must not take the resident
+        // daemon down with it, so errors here are logged and the loop keeps serving.
+        let result = stream
+            .map_err(CarouselError::Io)
+            .and_then(|stream| handle_switch_request(stream, &data, &dirty));
+        if let Err(error) = result {
+            eprintln!("warning: failed to handle a switch request: {error}");
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_switch_request(
+    mut stream: UnixStream,
+    data: &Arc<Mutex<CarouselData>>,
+    dirty: &Arc<AtomicBool>,
+) -> CarouselResult<()> {
+    let mut message = String::new();
+    stream.read_to_string(&mut message)?;
+    if message.trim() != "switch" {
+        return Ok(());
+    }
+
+    let call_time = std::time::SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time after UNIX epoch")
+        .as_secs_f64();
+    let mut action_socket = Socket::connect()?;
+    let mut data = data.lock().expect("carousel data lock");
+
+    // INFO: check is single layout in system to avoid useless computations.
+    if data.layouts.len() < 2 {
+        return Ok(());
+    }
+
+    data.compute_time_and_count(call_time);
+    data.handle_switch();
+    action_socket.send(Request::Action(niri_ipc::Action::SwitchLayout {
+        layout: niri_ipc::LayoutSwitchTarget::Index(data.layouts[data.cursor] as u8),
+    }))??;
+    dirty.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Watches niri's event stream on a background thread and keeps `data` consistent with
+/// layout changes made through other niri keybinds, which would otherwise desync silently.
+fn spawn_event_watcher(data: Arc<Mutex<CarouselData>>, dirty: Arc<AtomicBool>) -> CarouselResult<()> {
+    let mut event_socket = Socket::connect()?;
+    let Response::Handled = event_socket.send(Request::EventStream)?? else {
+        return Err(CarouselError::IpcProblems);
+    };
+
+    std::thread::spawn(move || {
+        let mut read_event = event_socket.read_events();
+        while let Ok(event) = read_event() {
+            let mut data = data.lock().expect("carousel data lock");
+            match event {
+                Event::KeyboardLayoutSwitched { idx } => {
+                    // INFO: the daemon's own `SwitchLayout` comes back here too; it is already
+                    // reflected by `cursor`, so only a switch to another layout is external.
+                    if data.layouts.get(data.cursor) == Some(&(idx as usize)) {
+                        continue;
+                    }
+                    // INFO: an external switch is itself a commit: the new layout becomes
+                    // the MRU head so a subsequent single tap returns to what preceded it.
+                    if let Some(pos) = data.layouts.iter().position(|&l| l == idx as usize) {
+                        let committed = data.layouts.remove(pos);
+                        data.layouts.insert(0, committed);
+                        data.cursor = 0;
+                    }
+                    dirty.store(true, Ordering::Relaxed);
+                }
+                Event::KeyboardLayoutsChanged { keyboard_layouts } => {
+                    data.layouts = (0..keyboard_layouts.names.len()).collect();
+                    data.cursor = 0;
+                    dirty.store(true, Ordering::Relaxed);
+                }
+                _ => {}
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Periodically flushes `data` to disk when it's been touched, instead of on every
+/// single switch.
+fn spawn_periodic_flush(data: Arc<Mutex<CarouselData>>, dirty: Arc<AtomicBool>) {
+    const FLUSH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+    std::thread::spawn(move || {
+        loop {
+            std::thread::sleep(FLUSH_INTERVAL);
+            if dirty.swap(false, Ordering::Relaxed) {
+                let data = data.lock().expect("carousel data lock");
+                let _ = data.dump();
+            }
+        }
+    });
+}
+
 fn main() -> CarouselResult<()> {
     LayoutCarouselCmd::parse().handle()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data_with(
+        layouts: Vec<usize>,
+        cursor: usize,
+        counter: u8,
+        single_tap_toggles: bool,
+    ) -> CarouselData {
+        CarouselData {
+            version: CURRENT_SCHEMA_VERSION,
+            last_time: 0.0,
+            layouts,
+            cursor,
+            sum_time: 0.0,
+            counter,
+            max_duration: Duration::default(),
+            single_tap_toggles,
+        }
+    }
+
+    #[test]
+    fn compute_time_and_count_resets_sum_time_per_tap() {
+        let mut data = data_with(vec![0, 1, 2], 0, 0, true);
+
+        data.compute_time_and_count(0.1);
+        data.compute_time_and_count(0.25);
+        data.compute_time_and_count(0.45);
+
+        // Gaps of 0.1s/0.15s/0.2s each sit comfortably under the 0.4s default window, so the
+        // sequence must keep counting taps instead of committing early from an additively
+        // accumulated `sum_time`.
+        assert_eq!(data.counter, 3);
+        assert_eq!(data.layouts, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn compute_time_and_count_commits_mru_head_once_window_elapses() {
+        let mut data = data_with(vec![0, 1, 2], 2, 2, true);
+
+        data.compute_time_and_count(1.0);
+
+        assert_eq!(data.layouts, vec![2, 0, 1]);
+        assert_eq!(data.counter, 1);
+        assert_eq!(data.cursor, 0);
+    }
+
+    #[test]
+    fn handle_switch_single_tap_toggles_to_previous_layout() {
+        let mut data = data_with(vec![0, 1, 2], 0, 1, true);
+        data.handle_switch();
+        assert_eq!(data.cursor, 1);
+    }
+
+    #[test]
+    fn handle_switch_single_tap_holds_when_toggle_disabled() {
+        let mut data = data_with(vec![0, 1, 2], 0, 1, false);
+        data.handle_switch();
+        assert_eq!(data.cursor, 0);
+    }
+
+    #[test]
+    fn handle_switch_cycles_forward_through_mru_on_repeat_taps() {
+        let mut data = data_with(vec![0, 1, 2], 1, 2, true);
+        data.handle_switch();
+        assert_eq!(data.cursor, 2);
+        data.handle_switch();
+        assert_eq!(data.cursor, 0);
+    }
+}